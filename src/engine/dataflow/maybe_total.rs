@@ -0,0 +1,111 @@
+//! Dispatches a handful of operators (`threshold`, and the `count`/`distinct` built on top of it)
+//! to a cheaper implementation when a scope's timestamps are known to be totally ordered, without
+//! forcing every caller to carry a `Timestamp: TotalOrder` bound themselves.
+//!
+//! Rust can't branch on a trait bound at runtime, so the choice is made at the type level instead:
+//! every [`MaybeTotalScope`] names an [`IsTotal`] implementation (either [`Total`] or [`NotTotal`])
+//! picked once, when the scope is constructed, and [`MaybeTotal`](super::operators::MaybeTotal)
+//! simply forwards to `S::IsTotal::threshold`.
+
+use differential_dataflow::difference::Semigroup;
+use differential_dataflow::operators::arrange::Arranged;
+use differential_dataflow::operators::{Threshold, ThresholdTotal};
+use differential_dataflow::trace::TraceReader;
+use differential_dataflow::{Collection, Data};
+use timely::dataflow::Scope;
+use timely::order::TotalOrder;
+
+/// Picks, at the type level, which [`IsTotal`] implementation a scope's timestamp uses.
+pub trait MaybeTotalSwitch: Scope {
+    type IsTotal: IsTotal<Self>;
+}
+
+/// A scope usable by the engine's core dataflow operators: one that has picked an [`IsTotal`] arm
+/// for its timestamp type.
+pub trait MaybeTotalScope: MaybeTotalSwitch {}
+
+impl<S> MaybeTotalScope for S where S: MaybeTotalSwitch {}
+
+/// The totally-ordered-vs-partially-ordered dispatch target for `threshold`/`count`/`distinct`.
+pub trait IsTotal<S: Scope> {
+    /// Maps the accumulated weight of each key to an output diff via `logic`, re-evaluating a key
+    /// only when its accumulated weight actually changes.
+    fn threshold<T, R2>(
+        arranged: &Arranged<S, T>,
+        logic: impl FnMut(&T::Key, &T::R) -> R2 + 'static,
+    ) -> Collection<S, (T::Key, R2), isize>
+    where
+        T: TraceReader<Val = (), Time = S::Timestamp> + Clone + 'static,
+        T::Key: Data,
+        T::R: Semigroup,
+        R2: Semigroup + Data;
+
+    fn count<T>(arranged: &Arranged<S, T>) -> Collection<S, (T::Key, T::R), isize>
+    where
+        T: TraceReader<Val = (), Time = S::Timestamp> + Clone + 'static,
+        T::Key: Data,
+        T::R: Semigroup,
+    {
+        Self::threshold(arranged, |_key, diff| diff.clone())
+    }
+
+    fn distinct<T>(arranged: &Arranged<S, T>) -> Collection<S, T::Key, isize>
+    where
+        T: TraceReader<Val = (), Time = S::Timestamp> + Clone + 'static,
+        T::Key: Data,
+        T::R: Semigroup,
+    {
+        Self::threshold(arranged, |_key, _diff| ()).map(|(key, ())| key)
+    }
+}
+
+/// Dispatch target for scopes whose timestamp is only a partial order: uses
+/// differential-dataflow's general `Threshold`, which tracks per-time deltas explicitly.
+pub struct NotTotal;
+
+impl<S: Scope> IsTotal<S> for NotTotal {
+    fn threshold<T, R2>(
+        arranged: &Arranged<S, T>,
+        logic: impl FnMut(&T::Key, &T::R) -> R2 + 'static,
+    ) -> Collection<S, (T::Key, R2), isize>
+    where
+        T: TraceReader<Val = (), Time = S::Timestamp> + Clone + 'static,
+        T::Key: Data,
+        T::R: Semigroup,
+        R2: Semigroup + Data,
+    {
+        // `differential_dataflow::operators::Threshold::threshold` already emits a `(key,
+        // logic(key, weight))` pair per update, at the standard per-snapshot `isize` multiplicity
+        // (the same shape its own `count`/`distinct` are built on) — not `logic`'s output type
+        // used as the diff. That's what lets this delegate straight to the return type above
+        // instead of re-materializing `(key, weight)` pairs itself.
+        arranged.threshold(logic)
+    }
+}
+
+/// Dispatch target for scopes whose timestamp is totally ordered: uses
+/// differential-dataflow's `ThresholdTotal`, which only needs the total accumulation per key
+/// (no per-time bookkeeping) since updates are guaranteed to arrive in time order.
+pub struct Total;
+
+impl<S: Scope> IsTotal<S> for Total
+where
+    S::Timestamp: TotalOrder,
+{
+    fn threshold<T, R2>(
+        arranged: &Arranged<S, T>,
+        logic: impl FnMut(&T::Key, &T::R) -> R2 + 'static,
+    ) -> Collection<S, (T::Key, R2), isize>
+    where
+        T: TraceReader<Val = (), Time = S::Timestamp> + Clone + 'static,
+        T::Key: Data,
+        T::R: Semigroup,
+        R2: Semigroup + Data,
+    {
+        // Same shape as `NotTotal::threshold` above: `ThresholdTotal::threshold_total` emits
+        // `(key, logic(key, weight))` pairs at `isize` multiplicity, it just gets there without
+        // per-time bookkeeping since `S::Timestamp: TotalOrder` guarantees updates arrive in
+        // time order.
+        arranged.threshold_total(logic)
+    }
+}
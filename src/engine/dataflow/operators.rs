@@ -1,3 +1,5 @@
+pub mod cross_join;
+pub mod delta_join;
 pub mod gradual_broadcast;
 pub mod output;
 pub mod prev_next;
@@ -6,18 +8,28 @@ pub mod time_column;
 mod utils;
 
 use std::any::type_name;
+use std::collections::VecDeque;
+use std::ops::Mul;
 use std::panic::Location;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
-use differential_dataflow::difference::Semigroup;
+use columnation::Columnation;
+use differential_dataflow::difference::{Abelian, Semigroup};
 use differential_dataflow::operators::arrange::{Arranged, TraceAgent};
+use differential_dataflow::operators::join::JoinCore;
 use differential_dataflow::trace::{Batch, Trace, TraceReader};
 use differential_dataflow::{AsCollection, Collection, Data, ExchangeData};
 use futures::stream::FuturesUnordered;
+use futures::task::{waker_ref, ArcWake};
+use futures::Future;
 use futures::StreamExt;
-use futures::{future, Future};
 use timely::dataflow::channels::pact::{Exchange, Pipeline};
+use timely::dataflow::operators::Capability;
 use timely::dataflow::operators::Exchange as _;
 use timely::dataflow::operators::Operator;
+use timely::scheduling::{Scheduler, SyncActivator};
 
 use crate::engine::BatchWrapper;
 
@@ -45,6 +57,29 @@ where
     where
         Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
         Tr::Batch: Batch;
+
+    /// Like [`arrange`](Self::arrange), but `Tr` is expected to be one of the
+    /// `differential_dataflow` spines backed by [`columnation`] regions (e.g. `ColValSpine`)
+    /// rather than an `Ord*Spine`, so batches land in a handful of large columnar allocations
+    /// instead of one heap allocation per record. The `K: Columnation, V: Columnation` bounds are
+    /// what those spines require of their key/value types.
+    #[track_caller]
+    fn arrange_flat<Tr>(&self) -> Arranged<S, TraceAgent<Tr>>
+    where
+        K: Columnation,
+        V: Columnation,
+        Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr::Batch: Batch,
+    {
+        self.arrange_flat_named("Arrange")
+    }
+
+    fn arrange_flat_named<Tr>(&self, name: &str) -> Arranged<S, TraceAgent<Tr>>
+    where
+        K: Columnation,
+        V: Columnation,
+        Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr::Batch: Batch;
 }
 
 pub trait ArrangeWithTypesSharded<S, K, V, R>
@@ -74,6 +109,35 @@ where
     where
         Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
         Tr::Batch: Batch;
+
+    /// Like [`arrange_sharded`](Self::arrange_sharded), but `Tr` is expected to be a
+    /// [`columnation`]-backed spine, so batches are packed into a handful of large columnar
+    /// allocations rather than cloned one record at a time into `Vec`-of-tuples. The sharding
+    /// function is respected exactly as in `arrange_sharded`, so no extra exchange is inserted.
+    #[track_caller]
+    fn arrange_flat_sharded<Tr>(
+        &self,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Arranged<S, TraceAgent<Tr>>
+    where
+        K: Columnation,
+        V: Columnation,
+        Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr::Batch: Batch,
+    {
+        self.arrange_flat_sharded_named("Arrange", sharding)
+    }
+
+    fn arrange_flat_sharded_named<Tr>(
+        &self,
+        name: &str,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Arranged<S, TraceAgent<Tr>>
+    where
+        K: Columnation,
+        V: Columnation,
+        Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr::Batch: Batch;
 }
 
 impl<T, S, K, V, R> ArrangeWithTypes<S, K, V, R> for T
@@ -92,6 +156,17 @@ where
     {
         self.arrange_sharded_named(name, Shard::shard)
     }
+
+    #[track_caller]
+    fn arrange_flat_named<Tr>(&self, name: &str) -> Arranged<S, TraceAgent<Tr>>
+    where
+        K: Columnation,
+        V: Columnation,
+        Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr::Batch: Batch,
+    {
+        self.arrange_flat_sharded_named(name, Shard::shard)
+    }
 }
 
 impl<T, S, K, V, R> ArrangeWithTypesSharded<S, K, V, R> for T
@@ -125,6 +200,35 @@ where
             self, exchange, &name,
         )
     }
+
+    #[track_caller]
+    fn arrange_flat_sharded_named<Tr>(
+        &self,
+        name: &str,
+        mut sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Arranged<S, TraceAgent<Tr>>
+    where
+        K: Columnation,
+        V: Columnation,
+        Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr::Batch: Batch,
+    {
+        let caller = Location::caller();
+        let name = format!(
+            "{name} [{key}, {value}] (flat) at {caller}",
+            key = type_name::<K>(),
+            value = type_name::<V>()
+        );
+        // The exchange pact is identical to `arrange_sharded_named`; the memory win comes
+        // entirely from `Tr` being a columnation-backed spine (its batch builder is what lands
+        // records into columnar regions), so sharding semantics are unchanged.
+        let exchange =
+            Exchange::new(move |((key, _value), _time, _diff): &((K, V), _, _)| sharding(key));
+        #[allow(clippy::disallowed_methods)]
+        differential_dataflow::operators::arrange::arrangement::Arrange::arrange_core(
+            self, exchange, &name,
+        )
+    }
 }
 
 pub trait MaybeTotal<S, K, R>
@@ -133,6 +237,18 @@ where
     K: Data,
     R: Semigroup,
 {
+    /// Maps the accumulated weight of each key to an output diff via `logic`, dispatched through
+    /// `S::IsTotal` exactly like `distinct`/`count` below (with a specialized totally-ordered
+    /// path): a key is only re-evaluated when its accumulated weight actually changes, so
+    /// monotone thresholds (e.g. "present iff weight >= 3") don't churn output on every update.
+    /// `count` and `distinct` are both special cases of `threshold`.
+    fn threshold<R2>(
+        &self,
+        logic: impl FnMut(&K, &R) -> R2 + 'static,
+    ) -> Collection<S, (K, R2), isize>
+    where
+        R2: Semigroup + Data;
+
     fn count(&self) -> Collection<S, (K, R), isize>;
 
     fn distinct(&self) -> Collection<S, K, isize>;
@@ -145,12 +261,22 @@ where
     T::Key: Data,
     T::R: Semigroup,
 {
+    fn threshold<R2>(
+        &self,
+        logic: impl FnMut(&T::Key, &T::R) -> R2 + 'static,
+    ) -> Collection<S, (T::Key, R2), isize>
+    where
+        R2: Semigroup + Data,
+    {
+        S::IsTotal::threshold(self, logic)
+    }
+
     fn count(&self) -> Collection<S, (T::Key, T::R), isize> {
-        S::IsTotal::count(self)
+        self.threshold(|_key, diff| diff.clone())
     }
 
     fn distinct(&self) -> Collection<S, T::Key, isize> {
-        S::IsTotal::distinct(self)
+        self.threshold(|_key, _diff| ()).map(|(key, ())| key)
     }
 }
 
@@ -160,6 +286,17 @@ where
     K: ExchangeData + Shard,
     R: Semigroup + ExchangeData,
 {
+    fn threshold<R2>(
+        &self,
+        logic: impl FnMut(&K, &R) -> R2 + 'static,
+    ) -> Collection<S, (K, R2), isize>
+    where
+        R2: Semigroup + Data,
+    {
+        let arranged: ArrangedBySelf<S, K, R> = self.arrange_named("Arrange: ThresholdMaybeTotal");
+        arranged.threshold(logic)
+    }
+
     fn count(&self) -> Collection<S, (K, R), isize> {
         let arranged: ArrangedBySelf<S, K, R> = self.arrange_named("Arrange: CountMaybeTotal");
         arranged.count()
@@ -171,6 +308,99 @@ where
     }
 }
 
+/// Semijoin/antijoin a `Collection` against a trace that was already arranged elsewhere (e.g. via
+/// `arrange_sharded` for a dimension table reused across many join sites), instead of re-arranging
+/// `self` into a fresh index each time. The pre-built trace is keyed by `K` with `Val = ()`, and
+/// its sharding function is respected so no extra exchange is inserted for `self`.
+pub trait JoinArranged<S, K, V, R>
+where
+    S: MaybeTotalScope,
+    K: ExchangeData + Shard,
+    V: ExchangeData,
+    R: Semigroup + ExchangeData,
+{
+    /// Restricts `self` to the `(K, V)` records whose key is present in `other`, multiplying
+    /// diffs. `Tr1` picks the trace implementation used to arrange `self` (keyed by `K` with the
+    /// real `Val = V`, unlike the by-self arrangements used for bare-key collections). `sharding`
+    /// must be the same sharding function `other` was built with (e.g. via `arrange_sharded`), so
+    /// that `self` lands on the same workers for a given key and no extra exchange is needed.
+    fn semijoin_arranged<R2, Tr1, Tr2>(
+        &self,
+        other: &Arranged<S, TraceAgent<Tr2>>,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, V), <R as Mul<R2>>::Output>
+    where
+        R2: Semigroup + ExchangeData,
+        R: Mul<R2>,
+        <R as Mul<R2>>::Output: Semigroup + ExchangeData,
+        Tr1: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr1::Batch: Batch,
+        Tr2: Trace + TraceReader<Key = K, Val = (), Time = S::Timestamp, R = R2> + 'static,
+        Tr2::Batch: Batch;
+
+    /// Keeps only the `(K, V)` records of `self` whose key is *absent* from `other`, by computing
+    /// the semijoin and adding its negation back to `self`. `sharding` is forwarded to
+    /// `semijoin_arranged` and must match the function `other` was arranged with.
+    fn antijoin_arranged<R2, Tr1, Tr2>(
+        &self,
+        other: &Arranged<S, TraceAgent<Tr2>>,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, V), R>
+    where
+        R: Abelian + Mul<R2, Output = R>,
+        R2: Semigroup + ExchangeData,
+        Tr1: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr1::Batch: Batch,
+        Tr2: Trace + TraceReader<Key = K, Val = (), Time = S::Timestamp, R = R2> + 'static,
+        Tr2::Batch: Batch;
+}
+
+impl<S, K, V, R> JoinArranged<S, K, V, R> for Collection<S, (K, V), R>
+where
+    S: MaybeTotalScope,
+    K: ExchangeData + Shard,
+    V: ExchangeData,
+    R: Semigroup + ExchangeData,
+{
+    #[track_caller]
+    fn semijoin_arranged<R2, Tr1, Tr2>(
+        &self,
+        other: &Arranged<S, TraceAgent<Tr2>>,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, V), <R as Mul<R2>>::Output>
+    where
+        R2: Semigroup + ExchangeData,
+        R: Mul<R2>,
+        <R as Mul<R2>>::Output: Semigroup + ExchangeData,
+        Tr1: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr1::Batch: Batch,
+        Tr2: Trace + TraceReader<Key = K, Val = (), Time = S::Timestamp, R = R2> + 'static,
+        Tr2::Batch: Batch,
+    {
+        let arranged: Arranged<S, TraceAgent<Tr1>> =
+            self.arrange_sharded_named("Arrange: SemijoinArranged", sharding);
+        arranged.join_core(other, |key, value, ()| Some((key.clone(), value.clone())))
+    }
+
+    #[track_caller]
+    fn antijoin_arranged<R2, Tr1, Tr2>(
+        &self,
+        other: &Arranged<S, TraceAgent<Tr2>>,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, V), R>
+    where
+        R: Abelian + Mul<R2, Output = R>,
+        R2: Semigroup + ExchangeData,
+        Tr1: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+        Tr1::Batch: Batch,
+        Tr2: Trace + TraceReader<Key = K, Val = (), Time = S::Timestamp, R = R2> + 'static,
+        Tr2::Batch: Batch,
+    {
+        let matches = self.semijoin_arranged::<R2, Tr1, Tr2>(other, sharding);
+        self.concat(&matches.negate())
+    }
+}
+
 pub trait MapWrapped<S, D, R>
 where
     S: MaybeTotalScope,
@@ -207,14 +437,33 @@ where
         logic: impl FnMut(D) -> D2 + 'static,
     ) -> Collection<S, D2, R>;
 
+    /// Default concurrency cap for [`map_async`](Self::map_async): enough to overlap a handful of
+    /// slow per-record futures (e.g. network lookups) without letting an unbounded number of them
+    /// pile up in memory.
+    const DEFAULT_MAX_IN_FLIGHT: usize = 32;
+
+    #[track_caller]
     fn map_named_async<F: Future>(
         &self,
         name: &str,
         logic: impl Fn(D) -> F + 'static,
     ) -> Collection<S, F::Output, R>
+    where
+        F::Output: Data,
+    {
+        self.map_named_async_bounded(name, Self::DEFAULT_MAX_IN_FLIGHT, logic)
+    }
+
+    fn map_named_async_bounded<F: Future>(
+        &self,
+        name: &str,
+        max_in_flight: usize,
+        logic: impl Fn(D) -> F + 'static,
+    ) -> Collection<S, F::Output, R>
     where
         F::Output: Data;
 
+    #[track_caller]
     fn map_async<F: Future>(&self, logic: impl Fn(D) -> F + 'static) -> Collection<S, F::Output, R>
     where
         F::Output: Data,
@@ -258,38 +507,74 @@ where
     }
 
     #[track_caller]
-    fn map_named_async<F: Future>(
+    fn map_named_async_bounded<F: Future>(
         &self,
         name: &str,
+        max_in_flight: usize,
         logic: impl Fn(D) -> F + 'static,
     ) -> Collection<S, F::Output, R>
     where
         F::Output: Data,
     {
+        /// Wakes the operator's [`SyncActivator`] so timely re-schedules it once a future that
+        /// completed from outside the worker thread (e.g. on a background async runtime) has
+        /// output ready to admit into the dataflow.
+        struct OperatorWaker(SyncActivator);
+
+        impl ArcWake for OperatorWaker {
+            fn wake_by_ref(arc_self: &Arc<Self>) {
+                let _ = arc_self.0.activate();
+            }
+        }
+
         let caller = Location::caller();
         let name = format!("{name} at {caller}");
-        let mut vector = Vec::new();
-        let mut result = Vec::new();
+        let scope = self.inner.scope();
+
         self.inner
-            .unary(Pipeline, &name, move |_, _| {
+            .unary_frontier(Pipeline, &name, move |_cap, info| {
+                let waker = Arc::new(OperatorWaker(scope.sync_activator_for(&info.address)));
+
+                let mut vector = Vec::new();
+                // Items that have arrived but are waiting for an in-flight slot to free up.
+                let mut pending: VecDeque<(D, Capability<S::Timestamp>, R)> = VecDeque::new();
+                // Futures currently being polled, each retaining the capability for its time
+                // until it resolves.
+                let mut in_flight: FuturesUnordered<
+                    Pin<Box<dyn Future<Output = (F::Output, Capability<S::Timestamp>, R)>>>,
+                > = FuturesUnordered::new();
+
                 move |input, output| {
-                    while let Some((time, data)) = input.next() {
+                    while let Some((capability, data)) = input.next() {
                         data.swap(&mut vector);
+                        for (data, time, diff) in vector.drain(..) {
+                            pending.push_back((data, capability.delayed(&time), diff));
+                        }
+                    }
 
-                        let futures: FuturesUnordered<_> = vector
-                            .drain(..)
-                            .map(|(data, time, diff)| async { (logic(data).await, time, diff) })
-                            .collect();
-
-                        assert!(result.is_empty());
-                        result.reserve(futures.len());
+                    while in_flight.len() < max_in_flight {
+                        let Some((data, capability, diff)) = pending.pop_front() else {
+                            break;
+                        };
+                        let future = logic(data);
+                        in_flight.push(Box::pin(async move { (future.await, capability, diff) }));
+                    }
 
-                        futures::executor::block_on(futures.for_each(|item| {
-                            result.push(item);
-                            future::ready(())
-                        }));
+                    let waker_ref = waker_ref(&waker);
+                    let mut context = Context::from_waker(&waker_ref);
+                    while let Poll::Ready(Some((value, capability, diff))) =
+                        in_flight.poll_next_unpin(&mut context)
+                    {
+                        let time = capability.time().clone();
+                        output.session(&capability).give((value, time, diff));
 
-                        output.session(&time).give_vec(&mut result);
+                        // Polling may have freed a slot; admit more of the backlog right away
+                        // instead of waiting for the next scheduling round.
+                        if let Some((data, capability, diff)) = pending.pop_front() {
+                            let future = logic(data);
+                            in_flight
+                                .push(Box::pin(async move { (future.await, capability, diff) }));
+                        }
                     }
                 }
             })
@@ -317,4 +602,279 @@ where
             .exchange(|(data, _time, _diff)| data.shard())
             .as_collection()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use differential_dataflow::input::InputSession;
+    use differential_dataflow::trace::implementations::ord_neu::{
+        ColKeySpine, OrdKeySpine, OrdValSpine,
+    };
+    use timely::dataflow::operators::{Inspect, Probe};
+
+    use super::*;
+
+    // `delta_join`'s tests provide the blanket `MaybeTotalSwitch` impl for any totally-ordered
+    // scope that this test (and every other operator test in the crate) relies on.
+
+    #[test]
+    fn semijoin_arranged_retracts_when_other_loses_the_key() {
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let observed_inner = Rc::clone(&observed);
+
+        timely::execute_directly(move |worker| {
+            let mut left = InputSession::<usize, (usize, usize), isize>::new();
+            let mut right = InputSession::<usize, usize, isize>::new();
+
+            let probe = worker.dataflow(|scope| {
+                let left = left.to_collection(scope);
+                let right = right.to_collection(scope);
+                let other: Arranged<_, TraceAgent<OrdKeySpine<usize, usize, isize>>> =
+                    right.map(|key| (key, ())).arrange_named("Arrange: other");
+
+                left.semijoin_arranged::<isize, OrdValSpine<usize, usize, usize, isize>, _>(
+                    &other,
+                    Shard::shard,
+                )
+                .inner
+                .inspect(move |(pair, time, diff)| {
+                    observed_inner.borrow_mut().push((*pair, *time, *diff));
+                })
+                .probe()
+            });
+
+            left.advance_to(0);
+            left.insert((1, 10));
+            left.flush();
+            right.advance_to(0);
+            right.insert(1);
+            right.flush();
+            worker.step_while(|| probe.less_than(left.time()) || probe.less_than(right.time()));
+
+            // Retracting the only key `other` holds must retract every match it produced, not
+            // just stop proposing new ones.
+            right.advance_to(1);
+            right.remove(1);
+            right.flush();
+            left.advance_to(1);
+            left.flush();
+            worker.step_while(|| probe.less_than(right.time()));
+        });
+
+        let observed = observed.borrow();
+        assert!(
+            observed.contains(&((1, 10), 0, 1)),
+            "expected the initial semijoin to include (1, 10): {observed:?}"
+        );
+        assert!(
+            observed.contains(&((1, 10), 1, -1)),
+            "expected removing key 1 from `other` to retract (1, 10): {observed:?}"
+        );
+    }
+
+    #[test]
+    fn antijoin_arranged_keeps_only_keys_absent_from_other() {
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let observed_inner = Rc::clone(&observed);
+
+        timely::execute_directly(move |worker| {
+            let mut left = InputSession::<usize, (usize, usize), isize>::new();
+            let mut right = InputSession::<usize, usize, isize>::new();
+
+            let probe = worker.dataflow(|scope| {
+                let left = left.to_collection(scope);
+                let right = right.to_collection(scope);
+                let other: Arranged<_, TraceAgent<OrdKeySpine<usize, usize, isize>>> =
+                    right.map(|key| (key, ())).arrange_named("Arrange: other");
+
+                left.antijoin_arranged::<isize, OrdValSpine<usize, usize, usize, isize>, _>(
+                    &other,
+                    Shard::shard,
+                )
+                // The raw antijoin stream carries `self`'s +1 and the negated match's -1 for
+                // (1, 10) at time 0; consolidating observes the collection's net content.
+                .consolidate()
+                .inner
+                .inspect(move |(pair, time, diff)| {
+                    observed_inner.borrow_mut().push((*pair, *time, *diff));
+                })
+                .probe()
+            });
+
+            left.advance_to(0);
+            left.insert((1, 10));
+            left.flush();
+            right.advance_to(0);
+            right.insert(1);
+            right.flush();
+            worker.step_while(|| probe.less_than(left.time()) || probe.less_than(right.time()));
+
+            // Key 1 is present in `other`, so the antijoin must not have proposed (1, 10) yet.
+            // Once `other` loses that key, (1, 10) should appear.
+            right.advance_to(1);
+            right.remove(1);
+            right.flush();
+            left.advance_to(1);
+            left.flush();
+            worker.step_while(|| probe.less_than(right.time()));
+        });
+
+        let observed = observed.borrow();
+        assert!(
+            !observed.iter().any(|(pair, time, _diff)| *pair == (1, 10) && *time == 0),
+            "expected (1, 10) to be excluded while key 1 is present in `other`: {observed:?}"
+        );
+        assert!(
+            observed.contains(&((1, 10), 1, 1)),
+            "expected (1, 10) to appear once key 1 is removed from `other`: {observed:?}"
+        );
+    }
+
+    #[test]
+    fn arrange_flat_matches_arrange_for_count() {
+        let observed_regular = Rc::new(RefCell::new(Vec::new()));
+        let observed_flat = Rc::new(RefCell::new(Vec::new()));
+        let observed_regular_inner = Rc::clone(&observed_regular);
+        let observed_flat_inner = Rc::clone(&observed_flat);
+
+        timely::execute_directly(move |worker| {
+            let mut input = InputSession::<usize, (usize, usize), isize>::new();
+
+            let (probe_regular, probe_flat) = worker.dataflow(|scope| {
+                let collection = input.to_collection(scope);
+
+                // `count` works on key-only arrangements, so the values are dropped first.
+                let keys = collection.map(|(key, _value)| (key, ()));
+                let regular: Arranged<_, TraceAgent<OrdKeySpine<usize, usize, isize>>> =
+                    keys.arrange_named("Arrange: regular");
+                let flat: Arranged<_, TraceAgent<ColKeySpine<usize, usize, isize>>> =
+                    keys.arrange_flat_named("Arrange: flat");
+
+                let probe_regular = regular
+                    .count()
+                    .inner
+                    .inspect(move |((key, count), time, diff)| {
+                        observed_regular_inner.borrow_mut().push((*key, *count, *time, *diff));
+                    })
+                    .probe();
+                let probe_flat = flat
+                    .count()
+                    .inner
+                    .inspect(move |((key, count), time, diff)| {
+                        observed_flat_inner.borrow_mut().push((*key, *count, *time, *diff));
+                    })
+                    .probe();
+
+                (probe_regular, probe_flat)
+            });
+
+            input.advance_to(0);
+            input.insert((1, 10));
+            input.insert((1, 20));
+            input.flush();
+            worker.step_while(|| {
+                probe_regular.less_than(input.time()) || probe_flat.less_than(input.time())
+            });
+
+            input.advance_to(1);
+            input.remove((1, 10));
+            input.flush();
+            worker.step_while(|| {
+                probe_regular.less_than(input.time()) || probe_flat.less_than(input.time())
+            });
+        });
+
+        // `arrange_flat_named` differs from `arrange_named` only in the columnation-backed spine
+        // it builds, so the two must agree on every update down to the diff, not just the final
+        // count.
+        let observed_regular = observed_regular.borrow();
+        let observed_flat = observed_flat.borrow();
+        assert_eq!(
+            *observed_regular, *observed_flat,
+            "expected the flat arrangement's `count` to match the regular arrangement's"
+        );
+        assert!(
+            observed_regular.contains(&(1, 2, 0, 1)),
+            "expected key 1 to be counted twice after both inserts: {observed_regular:?}"
+        );
+    }
+
+    /// A future that resolves on its *second* poll rather than its first, incrementing a shared
+    /// counter when it starts and decrementing it when it finishes. Driving several of these
+    /// through `map_named_async_bounded` lets a test observe how many were in flight at once
+    /// without needing a real async runtime.
+    struct CountingFuture {
+        current: Rc<Cell<usize>>,
+        max_seen: Rc<Cell<usize>>,
+        value: usize,
+        polled: bool,
+    }
+
+    impl Future for CountingFuture {
+        type Output = usize;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<usize> {
+            if !self.polled {
+                self.polled = true;
+                let count = self.current.get() + 1;
+                self.current.set(count);
+                if count > self.max_seen.get() {
+                    self.max_seen.set(count);
+                }
+                // Ask to be polled again immediately, rather than staying `Pending` forever, so
+                // the future resolves within a handful of scheduling rounds.
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            } else {
+                self.current.set(self.current.get() - 1);
+                Poll::Ready(self.value)
+            }
+        }
+    }
+
+    #[test]
+    fn map_named_async_bounded_never_exceeds_max_in_flight() {
+        const MAX_IN_FLIGHT: usize = 3;
+
+        let current = Rc::new(Cell::new(0usize));
+        let max_seen = Rc::new(Cell::new(0usize));
+        let current_for_dataflow = Rc::clone(&current);
+        let max_seen_for_dataflow = Rc::clone(&max_seen);
+
+        timely::execute_directly(move |worker| {
+            let mut input = InputSession::<usize, usize, isize>::new();
+            let current = Rc::clone(&current_for_dataflow);
+            let max_seen = Rc::clone(&max_seen_for_dataflow);
+
+            let probe = worker.dataflow(|scope| {
+                let collection = input.to_collection(scope);
+                collection
+                    .map_named_async_bounded("test", MAX_IN_FLIGHT, move |value| CountingFuture {
+                        current: Rc::clone(&current),
+                        max_seen: Rc::clone(&max_seen),
+                        value,
+                        polled: false,
+                    })
+                    .inner
+                    .probe()
+            });
+
+            input.advance_to(0);
+            for value in 0..10 {
+                input.insert(value);
+            }
+            input.flush();
+            worker.step_while(|| probe.less_than(input.time()));
+        });
+
+        assert!(
+            max_seen.get() <= MAX_IN_FLIGHT,
+            "observed {} futures in flight at once, expected at most {MAX_IN_FLIGHT}",
+            max_seen.get()
+        );
+        assert!(max_seen.get() > 0, "expected at least one future to actually run");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,201 @@
+//! Incremental cross (θ-)joins, i.e. joins with no shared key.
+//!
+//! [`Reshard`](super::Reshard) and plain `join` both assume the two sides can be lined up by
+//! exchanging on a key; a cross join has no key to exchange on; every left record must be paired
+//! with every right record. Naively that means one side has to land entirely on every worker.
+//! [`CrossJoin::cross_join`] gets there by arranging the right-hand side locally on each worker
+//! from a *broadcast* copy of its stream (via timely's broadcast pact) rather than exchanging it
+//! by key, while the left-hand side stays exactly as partitioned as it already was. Each worker
+//! then pairs its own local left records against the full (broadcast) right-hand index. Because
+//! the right-hand side is arranged, retractions are handled the same way any other arrangement
+//! handles them: a retracted right batch broadcasts with negative diffs and the trace reflects
+//! the retraction, so already-joined output is correctly retracted downstream too.
+use std::ops::Mul;
+
+use differential_dataflow::difference::Semigroup;
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::operators::arrange::arrangement::Arrange;
+use differential_dataflow::operators::arrange::{Arranged, TraceAgent};
+use differential_dataflow::trace::{Batch, Cursor, Trace, TraceReader};
+use differential_dataflow::{AsCollection, Collection, ExchangeData};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Broadcast, Capability, Operator};
+
+use super::super::maybe_total::MaybeTotalScope;
+
+/// Computes the full product of two collections with no join key, combining matched pairs with
+/// `logic` instead of materializing `(D1, D2)` tuples.
+pub trait CrossJoin<S, D1, R1>
+where
+    S: MaybeTotalScope,
+    D1: ExchangeData,
+    R1: Semigroup + ExchangeData,
+{
+    /// Pairs every record of `self` with every record of `other`. `Tr` picks the trace
+    /// implementation used to arrange the broadcast copy of `other` on each worker.
+    #[track_caller]
+    fn cross_join<D2, R2, DOut, Tr>(
+        &self,
+        other: &Collection<S, D2, R2>,
+        logic: impl FnMut(&D1, &D2) -> DOut + 'static,
+    ) -> Collection<S, DOut, <R1 as Mul<R2>>::Output>
+    where
+        D2: ExchangeData,
+        R2: Semigroup + ExchangeData,
+        R1: Mul<R2>,
+        <R1 as Mul<R2>>::Output: Semigroup + ExchangeData,
+        DOut: ExchangeData,
+        Tr: Trace + TraceReader<Key = (), Val = D2, Time = S::Timestamp, R = R2> + 'static,
+        Tr::Batch: Batch;
+}
+
+impl<S, D1, R1> CrossJoin<S, D1, R1> for Collection<S, D1, R1>
+where
+    S: MaybeTotalScope,
+    D1: ExchangeData,
+    R1: Semigroup + ExchangeData,
+{
+    #[track_caller]
+    fn cross_join<D2, R2, DOut, Tr>(
+        &self,
+        other: &Collection<S, D2, R2>,
+        mut logic: impl FnMut(&D1, &D2) -> DOut + 'static,
+    ) -> Collection<S, DOut, <R1 as Mul<R2>>::Output>
+    where
+        D2: ExchangeData,
+        R2: Semigroup + ExchangeData,
+        R1: Mul<R2>,
+        <R1 as Mul<R2>>::Output: Semigroup + ExchangeData,
+        DOut: ExchangeData,
+        Tr: Trace + TraceReader<Key = (), Val = D2, Time = S::Timestamp, R = R2> + 'static,
+        Tr::Batch: Batch,
+    {
+        let broadcast = other.map(|value| ((), value)).inner.broadcast().as_collection();
+        let arranged: Arranged<S, TraceAgent<Tr>> =
+            Arrange::arrange_core(&broadcast, Pipeline, "Arrange: CrossJoinBroadcast");
+        let mut trace = arranged.trace.clone();
+
+        // `arranged.stream` is wired in as a genuine second input (rather than just cloning
+        // `trace` and reading it from a plain `unary`) so that timely's progress tracking holds
+        // a left-hand batch back until the broadcast right-hand arrangement has caught up to its
+        // time; otherwise the join could silently run against a right-hand side that hasn't
+        // received all of its updates yet.
+        self.inner
+            .binary_frontier(
+                &arranged.stream,
+                Pipeline,
+                Pipeline,
+                "CrossJoin",
+                move |_cap, _info| {
+                    let mut stash: Vec<(Capability<S::Timestamp>, Vec<(D1, S::Timestamp, R1)>)> =
+                        Vec::new();
+                    let mut buffer = Vec::new();
+
+                    move |left_input, right_input, output| {
+                        // Only drained to advance our dependency on the broadcast arrangement's
+                        // frontier; its contents are read through `trace`.
+                        right_input.for_each(|_capability, _data| {});
+
+                        left_input.for_each(|capability, data| {
+                            data.swap(&mut buffer);
+                            stash.push((capability.retain(), std::mem::take(&mut buffer)));
+                        });
+
+                        let frontier = right_input.frontier();
+                        stash.retain(|(capability, lefts)| {
+                            if frontier.less_equal(capability.time()) {
+                                return true;
+                            }
+                            let mut session = output.session(capability);
+                            for (left, left_time, left_diff) in lefts {
+                                let (mut cursor, storage) = trace.cursor();
+                                cursor.seek_key(&storage, &());
+                                if cursor.get_key(&storage) == Some(&()) {
+                                    while let Some(right) = cursor.get_val(&storage) {
+                                        cursor.map_times(&storage, |right_time, right_diff| {
+                                            let out = logic(left, right);
+                                            let out_time = left_time.join(right_time);
+                                            let out_diff = left_diff.clone() * right_diff.clone();
+                                            session.give((out, out_time, out_diff));
+                                        });
+                                        cursor.step_val(&storage);
+                                    }
+                                }
+                            }
+                            false
+                        });
+                    }
+                },
+            )
+            .as_collection()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use differential_dataflow::input::InputSession;
+    use differential_dataflow::trace::implementations::ord_neu::OrdValSpine;
+    use timely::dataflow::operators::{Inspect, Probe};
+
+    use super::*;
+
+    // `delta_join`'s tests provide the blanket `MaybeTotalSwitch` impl for any totally-ordered
+    // scope that this test (and every other operator test in the crate) relies on.
+
+    #[test]
+    fn cross_join_retracts_when_a_right_record_is_removed() {
+        let observed = Rc::new(RefCell::new(Vec::new()));
+        let observed_inner = Rc::clone(&observed);
+
+        timely::execute_directly(move |worker| {
+            let mut left = InputSession::<usize, usize, isize>::new();
+            let mut right = InputSession::<usize, usize, isize>::new();
+
+            let probe = worker.dataflow(|scope| {
+                let left = left.to_collection(scope);
+                let right = right.to_collection(scope);
+
+                left.cross_join::<_, _, _, OrdValSpine<(), usize, usize, isize>>(
+                    &right,
+                    |l, r| (*l, *r),
+                )
+                .inner
+                .inspect(move |(pair, time, diff)| {
+                    observed_inner.borrow_mut().push((*pair, *time, *diff));
+                })
+                .probe()
+            });
+
+            left.advance_to(0);
+            left.insert(1);
+            left.flush();
+            right.advance_to(0);
+            right.insert(10);
+            right.flush();
+            worker.step_while(|| probe.less_than(left.time()) || probe.less_than(right.time()));
+
+            // Retracting the only right-hand record must retract every pair it produced, not just
+            // stop producing new ones; a cross join that re-arranges `other` without reflecting
+            // retractions would leave `(1, 10)` live forever.
+            right.advance_to(1);
+            right.remove(10);
+            right.flush();
+            left.advance_to(1);
+            left.flush();
+            worker.step_while(|| probe.less_than(right.time()));
+        });
+
+        let observed = observed.borrow();
+        assert!(
+            observed.contains(&((1, 10), 0, 1)),
+            "expected the initial cross product to include (1, 10): {observed:?}"
+        );
+        assert!(
+            observed.contains(&((1, 10), 1, -1)),
+            "expected removing the right-hand record to retract (1, 10): {observed:?}"
+        );
+    }
+}
@@ -0,0 +1,502 @@
+//! Worst-case-optimal multiway joins via delta queries.
+//!
+//! A classical binary-join tree materializes an intermediate collection for every pairwise
+//! join, which can blow up even when the final answer is small (the "worst-case-optimal join"
+//! problem). Delta queries avoid this by incrementally maintaining the *changes* to an n-way
+//! join directly: a query `R1 ⋈ R2 ⋈ ... ⋈ Rn` is rewritten as a sum of n "delta flows", one per
+//! relation, where the i-th flow reacts only to changes in `Ri` and extends each such change
+//! against the other relations. Every output tuple is produced by exactly one flow: the flow
+//! looks up relations `< i` at their state *before* the triggering change and relations `> i` at
+//! their *current* state (including the change), using a strict/non-strict `comparison` on each
+//! side to pick which. Because every flow only ever proposes from the one relation that changed,
+//! no intermediate result of a full pairwise join tree is ever materialized.
+//!
+//! [`half_join`] is the building block shared by every delta flow: given a stream of proposed
+//! `(key, value, time)` updates and an already-[`arrange`](super::ArrangeWithTypes::arrange)d
+//! trace keyed the same way, it emits the join of each proposal against the trace entries whose
+//! time compares appropriately to the proposal's time, delayed to the join of both times so that
+//! results land on the correct frontier. Callers assembling the prior-state/current-state split
+//! that makes a delta query produce each output tuple exactly once call `half_join` directly, once
+//! per relation, with the `frontier_func`/`comparison` pair appropriate to that relation's
+//! position in the flow.
+//!
+//! [`propose`](Extend::propose)/[`validate`](Extend::validate)/[`count`](Extend::count) are a
+//! simpler *current-state* extension API on top of the same arranged traces: they always look a
+//! relation up as of the proposal's own time, with no prior/current split. That makes them a
+//! convenient way to build or validate a single prefix against "the relation as it stands now"
+//! (e.g. for planning, or for relations that are never on the "prior" side of a delta flow), but
+//! they are not a drop-in replacement for a hand-assembled chain of `half_join` calls in a query
+//! that needs that split.
+
+use std::ops::Mul;
+
+use differential_dataflow::difference::{Monoid, Semigroup};
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::operators::arrange::{Arranged, TraceAgent};
+use differential_dataflow::trace::cursor::Cursor;
+use differential_dataflow::trace::{Batch, Trace, TraceReader};
+use differential_dataflow::{AsCollection, Collection, ExchangeData};
+use timely::dataflow::channels::pact::{Exchange, Pipeline};
+use timely::dataflow::operators::{Capability, Operator};
+use timely::PartialOrder;
+
+use super::super::maybe_total::MaybeTotalScope;
+
+/// Sums the diffs recorded at every time up to and including `as_of` for the cursor's current
+/// key/value, returning `None` if that accumulated weight is zero (including "zero because nothing
+/// was ever recorded" and "zero because an earlier insert was fully retracted"). Reading only a
+/// cursor's structural presence and ignoring `map_times` would treat a retracted-to-zero value as
+/// if it were still live. Updates at later times that the trace already holds are not counted.
+fn accumulated_weight<C>(cursor: &mut C, storage: &C::Storage, as_of: &C::Time) -> Option<C::R>
+where
+    C: Cursor,
+    C::R: Monoid,
+    C::Time: PartialOrder,
+{
+    let mut total: Option<C::R> = None;
+    cursor.map_times(storage, |time, diff| {
+        if time.less_equal(as_of) {
+            match &mut total {
+                Some(sum) => sum.plus_equals(diff),
+                None => total = Some(diff.clone()),
+            }
+        }
+    });
+    total.filter(|sum| !sum.is_zero())
+}
+
+/// Joins a stream of proposed `(key, value, time)` updates against an arranged trace keyed by
+/// `K`, restricting matches to trace entries whose time `t'` satisfies `comparison(&t', &t)`.
+///
+/// Every match is delayed to `t.join(&t')`, so that a proposal joined against a relation's prior
+/// (or current) state still lands on a consistent, monotone timestamp.
+///
+/// A proposal arriving at time `t` can only be joined once the arrangement's own trace is known
+/// to reflect every update up to `t` — otherwise the join could silently miss updates that simply
+/// haven't arrived yet. `arrangement.stream` is therefore wired in as a genuine second input so
+/// that timely's progress tracking holds proposals back until the arrangement's frontier has
+/// passed `frontier_func(t)` (letting delta-flow callers that join against a relation's *prior*
+/// state delay by one step via `frontier_func`, rather than just `t` itself).
+///
+/// `sharding` must be the same function `arrangement` was built with (e.g. via `arrange_sharded`):
+/// proposals are exchanged by that same function so they land on the worker that holds the
+/// matching trace entries, rather than always using `K`'s default `Shard::shard`.
+#[track_caller]
+pub fn half_join<S, K, V, Tr, D2, CF, FF, L>(
+    proposals: &Collection<S, (K, V), isize>,
+    arrangement: Arranged<S, TraceAgent<Tr>>,
+    mut sharding: impl FnMut(&K) -> u64 + 'static,
+    frontier_func: FF,
+    comparison: CF,
+    mut logic: L,
+) -> Collection<S, D2, isize>
+where
+    S: MaybeTotalScope,
+    K: ExchangeData,
+    V: ExchangeData,
+    Tr: Trace + TraceReader<Key = K, Time = S::Timestamp, R = isize> + 'static,
+    Tr::Batch: Batch,
+    D2: ExchangeData,
+    CF: Fn(&S::Timestamp, &S::Timestamp) -> bool + 'static,
+    FF: Fn(&S::Timestamp) -> S::Timestamp + 'static,
+    L: FnMut(&K, &V, &Tr::Val) -> D2 + 'static,
+{
+    let mut trace = arrangement.trace.clone();
+    let exchange = Exchange::new(
+        move |((key, _value), _time, _diff): &((K, V), S::Timestamp, isize)| sharding(key),
+    );
+
+    proposals
+        .inner
+        .binary_frontier(
+            &arrangement.stream,
+            exchange,
+            Pipeline,
+            "HalfJoin",
+            move |_cap, _info| {
+                // Proposals we've received but can't yet join, because the arrangement hasn't
+                // caught up to their time, kept alongside the capability that holds our output
+                // open at that time.
+                let mut stash: Vec<(Capability<S::Timestamp>, Vec<((K, V), S::Timestamp, isize)>)> =
+                    Vec::new();
+                let mut buffer = Vec::new();
+
+                move |proposals_input, arrangement_input, output| {
+                    // We never read data out of this input directly (the trace handle already
+                    // sees every update); draining it is what makes timely track our dependency
+                    // on the arrangement's frontier.
+                    arrangement_input.for_each(|_capability, _data| {});
+
+                    proposals_input.for_each(|capability, data| {
+                        data.swap(&mut buffer);
+                        stash.push((capability.retain(), std::mem::take(&mut buffer)));
+                    });
+
+                    let frontier = arrangement_input.frontier();
+                    stash.retain(|(capability, proposals)| {
+                        if frontier.less_equal(&frontier_func(capability.time())) {
+                            return true;
+                        }
+                        let mut session = output.session(capability);
+                        for ((key, value), time, diff) in proposals {
+                            let (mut cursor, storage) = trace.cursor();
+                            cursor.seek_key(&storage, key);
+                            if cursor.get_key(&storage) == Some(key) {
+                                while let Some(other_value) = cursor.get_val(&storage) {
+                                    cursor.map_times(&storage, |other_time, other_diff| {
+                                        if comparison(other_time, time) {
+                                            let joined_time = time.join(other_time);
+                                            let out = logic(key, value, other_value);
+                                            session.give((out, joined_time, diff * other_diff));
+                                        }
+                                    });
+                                    cursor.step_val(&storage);
+                                }
+                            }
+                        }
+                        false
+                    });
+                }
+            },
+        )
+        .as_collection()
+}
+
+/// A relation usable as one term of a chain of generic-join extenders: given a collection of
+/// prefixes built from the relations joined so far, proposes candidate extensions, validates
+/// extensions proposed by other relations, and counts how many extensions each prefix would
+/// receive (so a planner can join against the most selective relation first).
+///
+/// Every method takes a `sharding` function that must match however `self` was actually built
+/// (e.g. via `arrange_sharded`): prefixes/extensions are exchanged by that same function so they
+/// land on the worker that holds the matching trace entries, rather than always using `K`'s
+/// default `Shard::shard`.
+pub trait Extend<S, K, V, R>
+where
+    S: MaybeTotalScope,
+    K: ExchangeData,
+    V: ExchangeData,
+    R: Monoid + ExchangeData + Mul<R, Output = R>,
+{
+    /// Returns, for each `key` in `prefixes`, the number of values this relation would propose
+    /// as an extension, without materializing the extensions themselves.
+    fn count(
+        &self,
+        prefixes: &Collection<S, K, R>,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, usize), R>;
+
+    /// Proposes, for each `key` in `prefixes`, every value this relation has stored under `key`
+    /// as a candidate extension.
+    fn propose(
+        &self,
+        prefixes: &Collection<S, K, R>,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, V), R>;
+
+    /// Restricts `extensions` (proposed by some other relation in the chain) to those whose
+    /// value this relation also stores under the matching key.
+    fn validate(
+        &self,
+        extensions: &Collection<S, (K, V), R>,
+        sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, V), R>;
+}
+
+impl<S, K, V, R, Tr> Extend<S, K, V, R> for Arranged<S, TraceAgent<Tr>>
+where
+    S: MaybeTotalScope,
+    K: ExchangeData,
+    V: ExchangeData,
+    R: Monoid + ExchangeData + Mul<R, Output = R>,
+    Tr: Trace + TraceReader<Key = K, Val = V, Time = S::Timestamp, R = R> + 'static,
+    Tr::Batch: Batch,
+{
+    fn count(
+        &self,
+        prefixes: &Collection<S, K, R>,
+        mut sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, usize), R> {
+        let mut trace = self.trace.clone();
+        let exchange =
+            Exchange::new(move |(key, _time, _diff): &(K, S::Timestamp, R)| sharding(key));
+        prefixes
+            .inner
+            .binary_frontier(
+                &self.stream,
+                exchange,
+                Pipeline,
+                "Extend::count",
+                move |_cap, _info| {
+                    // A prefix arriving at time `t` can only be counted once the arrangement's
+                    // trace reflects every update through `t` — the same race `half_join` guards
+                    // against, so we stash prefixes and gate them on `self.stream`'s frontier too.
+                    let mut stash: Vec<(Capability<S::Timestamp>, Vec<(K, S::Timestamp, R)>)> =
+                        Vec::new();
+                    let mut buffer = Vec::new();
+
+                    move |prefixes_input, arrangement_input, output| {
+                        arrangement_input.for_each(|_capability, _data| {});
+
+                        prefixes_input.for_each(|capability, data| {
+                            data.swap(&mut buffer);
+                            stash.push((capability.retain(), std::mem::take(&mut buffer)));
+                        });
+
+                        let frontier = arrangement_input.frontier();
+                        stash.retain(|(capability, prefixes)| {
+                            if frontier.less_equal(capability.time()) {
+                                return true;
+                            }
+                            let mut session = output.session(capability);
+                            for (key, t, d) in prefixes {
+                                let (mut cursor, storage) = trace.cursor();
+                                cursor.seek_key(&storage, key);
+                                let mut count = 0usize;
+                                if cursor.get_key(&storage) == Some(key) {
+                                    while cursor.get_val(&storage).is_some() {
+                                        // Only a value whose accumulated weight is still nonzero
+                                        // is a live extension; one retracted back to zero (but
+                                        // not yet physically compacted out of the batch) doesn't
+                                        // count.
+                                        if accumulated_weight(&mut cursor, &storage, t).is_some() {
+                                            count += 1;
+                                        }
+                                        cursor.step_val(&storage);
+                                    }
+                                }
+                                session.give(((key.clone(), count), t.clone(), d.clone()));
+                            }
+                            false
+                        });
+                    }
+                },
+            )
+            .as_collection()
+    }
+
+    fn propose(
+        &self,
+        prefixes: &Collection<S, K, R>,
+        mut sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, V), R> {
+        let mut trace = self.trace.clone();
+        let exchange =
+            Exchange::new(move |(key, _time, _diff): &(K, S::Timestamp, R)| sharding(key));
+        prefixes
+            .inner
+            .binary_frontier(
+                &self.stream,
+                exchange,
+                Pipeline,
+                "Extend::propose",
+                move |_cap, _info| {
+                    // Same frontier-gating as `count` above: a prefix can't be extended until the
+                    // arrangement is known to have caught up to its time.
+                    let mut stash: Vec<(Capability<S::Timestamp>, Vec<(K, S::Timestamp, R)>)> =
+                        Vec::new();
+                    let mut buffer = Vec::new();
+
+                    move |prefixes_input, arrangement_input, output| {
+                        arrangement_input.for_each(|_capability, _data| {});
+
+                        prefixes_input.for_each(|capability, data| {
+                            data.swap(&mut buffer);
+                            stash.push((capability.retain(), std::mem::take(&mut buffer)));
+                        });
+
+                        let frontier = arrangement_input.frontier();
+                        stash.retain(|(capability, prefixes)| {
+                            if frontier.less_equal(capability.time()) {
+                                return true;
+                            }
+                            let mut session = output.session(capability);
+                            for (key, t, d) in prefixes {
+                                let (mut cursor, storage) = trace.cursor();
+                                cursor.seek_key(&storage, key);
+                                if cursor.get_key(&storage) == Some(key) {
+                                    while let Some(value) = cursor.get_val(&storage) {
+                                        // Scale the prefix's diff by the value's true accumulated
+                                        // weight, so a value with multiplicity > 1 is proposed
+                                        // with that multiplicity rather than just once.
+                                        if let Some(weight) =
+                                            accumulated_weight(&mut cursor, &storage, t)
+                                        {
+                                            session.give((
+                                                (key.clone(), value.clone()),
+                                                t.clone(),
+                                                d.clone() * weight,
+                                            ));
+                                        }
+                                        cursor.step_val(&storage);
+                                    }
+                                }
+                            }
+                            false
+                        });
+                    }
+                },
+            )
+            .as_collection()
+    }
+
+    fn validate(
+        &self,
+        extensions: &Collection<S, (K, V), R>,
+        mut sharding: impl FnMut(&K) -> u64 + 'static,
+    ) -> Collection<S, (K, V), R> {
+        let mut trace = self.trace.clone();
+        let exchange = Exchange::new(
+            move |((key, _value), _time, _diff): &((K, V), S::Timestamp, R)| sharding(key),
+        );
+        extensions
+            .inner
+            .binary_frontier(
+                &self.stream,
+                exchange,
+                Pipeline,
+                "Extend::validate",
+                move |_cap, _info| {
+                    // Same frontier-gating as `count`/`propose`: an extension proposed by another
+                    // relation can't be validated until this relation's trace has caught up.
+                    let mut stash: Vec<(
+                        Capability<S::Timestamp>,
+                        Vec<((K, V), S::Timestamp, R)>,
+                    )> = Vec::new();
+                    let mut buffer = Vec::new();
+
+                    move |extensions_input, arrangement_input, output| {
+                        arrangement_input.for_each(|_capability, _data| {});
+
+                        extensions_input.for_each(|capability, data| {
+                            data.swap(&mut buffer);
+                            stash.push((capability.retain(), std::mem::take(&mut buffer)));
+                        });
+
+                        let frontier = arrangement_input.frontier();
+                        stash.retain(|(capability, extensions)| {
+                            if frontier.less_equal(capability.time()) {
+                                return true;
+                            }
+                            let mut session = output.session(capability);
+                            for ((key, value), t, d) in extensions {
+                                let (mut cursor, storage) = trace.cursor();
+                                cursor.seek_key(&storage, key);
+                                let mut weight = None;
+                                if cursor.get_key(&storage) == Some(key) {
+                                    while let Some(other_value) = cursor.get_val(&storage) {
+                                        if other_value == value {
+                                            weight = accumulated_weight(&mut cursor, &storage, t);
+                                            break;
+                                        }
+                                        cursor.step_val(&storage);
+                                    }
+                                }
+                                // A value whose weight has been retracted to zero must not
+                                // validate, even though its structural entry may still be present
+                                // in the batch.
+                                if let Some(weight) = weight {
+                                    session.give((
+                                        (key.clone(), value.clone()),
+                                        t.clone(),
+                                        d.clone() * weight,
+                                    ));
+                                }
+                            }
+                            false
+                        });
+                    }
+                },
+            )
+            .as_collection()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use differential_dataflow::input::InputSession;
+    use differential_dataflow::operators::Threshold;
+    use differential_dataflow::trace::implementations::ord_neu::OrdValSpine;
+    use timely::dataflow::operators::{Inspect, Probe};
+    use timely::dataflow::Scope;
+    use timely::order::TotalOrder;
+
+    use super::super::super::maybe_total::{MaybeTotalSwitch, Total};
+    use super::super::ArrangeWithTypesSharded;
+    use super::*;
+
+    // `MaybeTotalScope` is normally picked once by whatever concrete scope the engine builds its
+    // dataflows in; this test only needs *a* scope to exercise `Extend`, so it wires the
+    // totally-ordered arm directly to any scope with a totally-ordered timestamp.
+    impl<S: Scope> MaybeTotalSwitch for S
+    where
+        S::Timestamp: TotalOrder,
+    {
+        type IsTotal = Total;
+    }
+
+    // Deliberately not `Shard`'s default hash: this is the whole point of the test. If `count`
+    // silently fell back to hashing `key` itself (the bug this test guards against) instead of
+    // exchanging by the `sharding` function it was actually given, prefixes and the arrangement
+    // they're counted against would land on different workers and the join would see nothing.
+    fn sharding(key: &usize) -> u64 {
+        (*key as u64).wrapping_mul(2_654_435_761) ^ 1
+    }
+
+    #[test]
+    fn extend_count_forgets_a_retracted_value() {
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let observed_for_workers = Arc::clone(&observed);
+
+        timely::execute(timely::Config::process(3), move |worker| {
+            let observed = Arc::clone(&observed_for_workers);
+            let mut input = InputSession::<usize, (usize, usize), isize>::new();
+
+            let probe = worker.dataflow(|scope| {
+                let collection = input.to_collection(scope);
+                let arranged: Arranged<_, TraceAgent<OrdValSpine<usize, usize, usize, isize>>> =
+                    collection.arrange_sharded_named("Arrange: test", sharding);
+                let prefixes = collection.map(|(key, _value)| key).distinct();
+
+                arranged
+                    .count(&prefixes, sharding)
+                    .inner
+                    .inspect(move |((key, count), time, diff)| {
+                        observed.lock().unwrap().push((*key, *count, *time, *diff));
+                    })
+                    .probe()
+            });
+
+            // Inserted on a single worker; `sharding` is what's responsible for getting this
+            // record to wherever `prefixes` ends up, not which worker happened to receive it.
+            input.advance_to(0);
+            if worker.index() == 0 {
+                input.insert((1, 10));
+            }
+            input.flush();
+            worker.step_while(|| probe.less_than(input.time()));
+
+            // Retracting the only value stored under key 1 should drop its count back to zero,
+            // not leave it at 1 because the trace's structural entry for (1, 10) is still there.
+            input.advance_to(1);
+            if worker.index() == 0 {
+                input.remove((1, 10));
+            }
+            input.flush();
+            worker.step_while(|| probe.less_than(input.time()));
+        })
+        .unwrap();
+
+        let observed = observed.lock().unwrap();
+        assert!(
+            observed.contains(&(1, 1, 0, 1)),
+            "expected key 1 to be counted once after the initial insert: {observed:?}"
+        );
+        assert!(
+            observed.contains(&(1, 0, 1, -1)),
+            "expected key 1's count to drop to zero once its only value is retracted: {observed:?}"
+        );
+    }
+}